@@ -0,0 +1,223 @@
+//! Simulates a 2D constant-velocity target, smooths a batch of noisy
+//! position observations with [`adskalman::KalmanFilterNoControl::smooth`],
+//! and writes a CSV of ground truth, observations, and smoothed estimates
+//! to stdout.
+//!
+//! The output of this example is checked into `tests/data/offline_smoothing.csv`
+//! and used as a regression fixture by the integration tests.
+use na::allocator::Allocator;
+use na::dimension::{U2, U4};
+use na::{DefaultAllocator, OMatrix, RealField};
+use nalgebra as na;
+use nalgebra::{Const, OVector};
+use serde::Serialize;
+
+use adskalman::{
+    KalmanFilterNoControl, ObservationModel, StateAndCovariance, TransitionModelLinearNoControl,
+};
+
+#[derive(Debug, Serialize)]
+struct CsvRow {
+    t: f64,
+    true_x: f64,
+    true_y: f64,
+    true_xvel: f64,
+    true_yvel: f64,
+    obs_x: f64,
+    obs_y: f64,
+    est_x: f64,
+    est_y: f64,
+    est_xvel: f64,
+    est_yvel: f64,
+}
+
+struct ConstantVelocity2DModel<R>
+where
+    R: RealField,
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U4>,
+{
+    transition_model: OMatrix<R, U4, U4>,
+    transition_model_transpose: OMatrix<R, U4, U4>,
+    transition_noise_covariance: OMatrix<R, U4, U4>,
+}
+
+impl<R> ConstantVelocity2DModel<R>
+where
+    R: RealField + Copy,
+{
+    fn new(dt: R, noise_scale: R) -> Self {
+        let one = na::convert(1.0);
+        let zero = na::convert(0.0);
+        #[rustfmt::skip]
+        let transition_model = OMatrix::<R,U4,U4>::new(one, zero,  dt, zero,
+                            zero, one, zero,  dt,
+                            zero, zero, one, zero,
+                            zero, zero, zero, one);
+
+        let t33 = dt * dt * dt / na::convert(3.0);
+        let t22 = dt * dt / na::convert(2.0);
+        #[rustfmt::skip]
+        let transition_noise_covariance = OMatrix::<R,U4,U4>::new(t33, zero, t22, zero,
+                                        zero, t33, zero, t22,
+                                        t22, zero, dt, zero,
+                                        zero, t22, zero, dt)*noise_scale;
+        Self {
+            transition_model,
+            transition_model_transpose: transition_model.transpose(),
+            transition_noise_covariance,
+        }
+    }
+}
+
+impl<R> TransitionModelLinearNoControl<R, U4> for ConstantVelocity2DModel<R>
+where
+    R: RealField,
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U2, U4>,
+    DefaultAllocator: Allocator<R, U4, U2>,
+    DefaultAllocator: Allocator<R, U2, U2>,
+    DefaultAllocator: Allocator<R, U4>,
+{
+    fn F(&self) -> &OMatrix<R, U4, U4> {
+        &self.transition_model
+    }
+    fn FT(&self) -> &OMatrix<R, U4, U4> {
+        &self.transition_model_transpose
+    }
+    fn Q(&self) -> &OMatrix<R, U4, U4> {
+        &self.transition_noise_covariance
+    }
+}
+
+struct PositionObservationModel<R: RealField>
+where
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U2, U4>,
+    DefaultAllocator: Allocator<R, U4, U2>,
+    DefaultAllocator: Allocator<R, U2, U2>,
+    DefaultAllocator: Allocator<R, U4>,
+{
+    observation_matrix: OMatrix<R, U2, U4>,
+    observation_matrix_transpose: OMatrix<R, U4, U2>,
+    observation_noise_covariance: OMatrix<R, U2, U2>,
+}
+
+impl<R: RealField + Copy> PositionObservationModel<R> {
+    fn new(var: R) -> Self {
+        let one = na::convert(1.0);
+        let zero = na::convert(0.0);
+        #[rustfmt::skip]
+        let observation_matrix = OMatrix::<R,U2,U4>::new(one, zero, zero, zero,
+                                    zero, one, zero, zero);
+        #[rustfmt::skip]
+        let observation_noise_covariance = OMatrix::<R,U2,U2>::new(var, zero,
+                                                zero, var);
+        Self {
+            observation_matrix,
+            observation_matrix_transpose: observation_matrix.transpose(),
+            observation_noise_covariance,
+        }
+    }
+}
+
+impl<R: RealField> ObservationModel<R, U4, U2> for PositionObservationModel<R>
+where
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U2, U4>,
+    DefaultAllocator: Allocator<R, U4, U2>,
+    DefaultAllocator: Allocator<R, U2, U2>,
+    DefaultAllocator: Allocator<R, U4>,
+    DefaultAllocator: Allocator<R, U2>,
+{
+    fn H(&self) -> &OMatrix<R, U2, U4> {
+        &self.observation_matrix
+    }
+    fn HT(&self) -> &OMatrix<R, U4, U2> {
+        &self.observation_matrix_transpose
+    }
+    fn R(&self) -> &OMatrix<R, U2, U2> {
+        &self.observation_noise_covariance
+    }
+}
+
+/// A simple linear congruential generator so this example has no extra
+/// dependency on a random number crate and is fully reproducible.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 11) as f64) / ((1u64 << 53) as f64)
+    }
+
+    /// Approximately standard-normal via a sum of uniforms (Irwin-Hall).
+    fn next_gaussian(&mut self) -> f64 {
+        let sum: f64 = (0..12).map(|_| self.next_f64()).sum();
+        sum - 6.0
+    }
+}
+
+fn main() {
+    let dt = 0.01;
+    let noise_scale: f64 = 100.0;
+    let obs_var: f64 = 0.01;
+
+    let motion_model = ConstantVelocity2DModel::new(dt, noise_scale);
+    let observation_model = PositionObservationModel::new(obs_var);
+    let kf = KalmanFilterNoControl::new(&motion_model, &observation_model);
+
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+
+    let mut rng = Lcg(99);
+    let obs_std = obs_var.sqrt();
+
+    let mut true_state = true_initial_state;
+    let mut true_states = vec![];
+    let mut observations = vec![];
+    for _ in 0..50 {
+        true_state = motion_model.predict(&true_state);
+        true_states.push(true_state);
+
+        let obs_x = true_state[0] + rng.next_gaussian() * obs_std;
+        let obs_y = true_state[1] + rng.next_gaussian() * obs_std;
+        observations.push(OVector::<f64, Const<2>>::new(obs_x, obs_y));
+    }
+
+    let initial_estimate = StateAndCovariance::new(true_initial_state, initial_covariance);
+    let smoothed = kf.smooth(&initial_estimate, &observations).unwrap();
+
+    let mut wtr = csv::Writer::from_writer(std::io::stdout());
+    for (i, ((true_state, observation), estimate)) in true_states
+        .iter()
+        .zip(observations.iter())
+        .zip(smoothed.iter())
+        .enumerate()
+    {
+        let t = i as f64 * dt;
+        let est_state = estimate.state();
+        wtr.serialize(CsvRow {
+            t,
+            true_x: true_state[0],
+            true_y: true_state[1],
+            true_xvel: true_state[2],
+            true_yvel: true_state[3],
+            obs_x: observation[0],
+            obs_y: observation[1],
+            est_x: est_state[0],
+            est_y: est_state[1],
+            est_xvel: est_state[2],
+            est_yvel: est_state[3],
+        })
+        .unwrap();
+    }
+    wtr.flush().unwrap();
+}
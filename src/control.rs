@@ -0,0 +1,207 @@
+use na::allocator::Allocator;
+use na::{DefaultAllocator, Dim, OMatrix, OVector, RealField};
+use nalgebra as na;
+
+use crate::{
+    compute_kalman_gain, compute_posterior_covariance, compute_residual, CovarianceUpdateMethod,
+    Error, ObservationModel, Residual, Result, StateAndCovariance,
+};
+
+/// A linear, time-invariant state transition model with a control input.
+///
+/// Unlike [`crate::TransitionModelLinearNoControl`], which describes a
+/// process model `x̄ = F x` evolving on its own, this trait adds a control
+/// (or actuation) input `u`, so that `x̄ = F x + B u`. This covers actuated
+/// systems, such as a commanded acceleration or thrust, where the control
+/// input is known to the filter at each step rather than being part of the
+/// estimated state.
+pub trait TransitionModelLinearWithControl<R, SS, CS>
+where
+    R: RealField,
+    SS: Dim,
+    CS: Dim,
+    DefaultAllocator:
+        Allocator<R, SS, SS> + Allocator<R, SS, CS> + Allocator<R, SS> + Allocator<R, CS>,
+{
+    /// The state transition matrix.
+    fn F(&self) -> &OMatrix<R, SS, SS>;
+    /// The transpose of the state transition matrix.
+    fn FT(&self) -> &OMatrix<R, SS, SS>;
+    /// The control matrix.
+    fn B(&self) -> &OMatrix<R, SS, CS>;
+    /// The process noise covariance.
+    fn Q(&self) -> &OMatrix<R, SS, SS>;
+
+    /// Predict the state at the next time step given the current state and
+    /// the control input applied over this step.
+    fn predict(&self, state: &OVector<R, SS>, control: &OVector<R, CS>) -> OVector<R, SS> {
+        self.F() * state + self.B() * control
+    }
+}
+
+/// A Kalman filter for a linear, time-invariant system with a control input.
+///
+/// This mirrors [`crate::KalmanFilterNoControl`], except the predict step
+/// also takes a control vector `u` and computes `x̄ = F x + B u` via
+/// [`TransitionModelLinearWithControl`]. The observation side and the
+/// [`CovarianceUpdateMethod`] choices are unchanged; the control input only
+/// ever affects prediction.
+pub struct KalmanFilterWithControl<'a, R, SS, CS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    CS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, CS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, CS>
+        + Allocator<R, OS>,
+{
+    transition_model: &'a dyn TransitionModelLinearWithControl<R, SS, CS>,
+    observation_model: &'a dyn ObservationModel<R, SS, OS>,
+}
+
+impl<'a, R, SS, CS, OS> KalmanFilterWithControl<'a, R, SS, CS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    CS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, CS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, CS>
+        + Allocator<R, OS>,
+{
+    /// Create a new filter from a transition model (with control input) and
+    /// an observation model.
+    pub fn new(
+        transition_model: &'a dyn TransitionModelLinearWithControl<R, SS, CS>,
+        observation_model: &'a dyn ObservationModel<R, SS, OS>,
+    ) -> Self {
+        Self {
+            transition_model,
+            observation_model,
+        }
+    }
+
+    /// Predict the next state and covariance from the previous estimate and
+    /// the control input applied over this step.
+    fn predict(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        control: &OVector<R, CS>,
+    ) -> StateAndCovariance<R, SS> {
+        let f = self.transition_model.F();
+        let state = self
+            .transition_model
+            .predict(previous_estimate.state(), control);
+        let covariance = f * previous_estimate.covariance() * self.transition_model.FT()
+            + self.transition_model.Q();
+        StateAndCovariance::new(state, covariance)
+    }
+
+    /// Compute the residual of an observation against a prior estimate.
+    fn residual(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+    ) -> Residual<R, OS> {
+        let innovation = observation - self.observation_model.predict_observation(prior.state());
+        compute_residual(
+            prior.covariance(),
+            self.observation_model.H(),
+            self.observation_model.HT(),
+            self.observation_model.R(),
+            innovation,
+        )
+    }
+
+    /// Update a prior (predicted) estimate with an observation.
+    fn update(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        covariance_update_method: CovarianceUpdateMethod,
+    ) -> Result<StateAndCovariance<R, SS>> {
+        let residual = self.residual(prior, observation);
+        let h = self.observation_model.H();
+        let ht = self.observation_model.HT();
+        let p = prior.covariance();
+        let r = self.observation_model.R();
+
+        let (state_correction, kalman_gain) = compute_kalman_gain(p, ht, &residual)?;
+        let state = prior.state() + state_correction;
+        let covariance =
+            compute_posterior_covariance(p, h, r, &kalman_gain, covariance_update_method);
+
+        Ok(StateAndCovariance::new(state, covariance))
+    }
+
+    /// Perform one full predict/update step given a control input and an
+    /// observation.
+    ///
+    /// Uses [`CovarianceUpdateMethod::OptimalKalman`] for the covariance
+    /// update, and performs no covariance health check. Use
+    /// [`Self::step_with_options`] to choose a different form or to reject
+    /// ill-conditioned estimates.
+    pub fn step(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        control: &OVector<R, CS>,
+        observation: &OVector<R, OS>,
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        self.step_with_options(
+            previous_estimate,
+            control,
+            observation,
+            CovarianceUpdateMethod::OptimalKalman,
+            None,
+        )
+    }
+
+    /// Perform one full predict/update step given a control input and an
+    /// observation.
+    ///
+    /// As with [`crate::KalmanFilterNoControl::step_with_options`], an
+    /// observation containing any `NaN` component is treated as missing and
+    /// the predicted (prior) estimate is returned unchanged, and a `Some`
+    /// `rcond_floor` rejects an ill-conditioned resulting estimate with
+    /// [`Error::CovarianceIllConditioned`].
+    pub fn step_with_options(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        control: &OVector<R, CS>,
+        observation: &OVector<R, OS>,
+        covariance_update_method: CovarianceUpdateMethod,
+        rcond_floor: Option<R>,
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        let prior = self.predict(previous_estimate, control);
+        let estimate = if observation.iter().any(|v| v.clone() != v.clone()) {
+            prior
+        } else {
+            self.update(&prior, observation, covariance_update_method)?
+        };
+        if let Some(floor) = rcond_floor {
+            if estimate.reciprocal_condition() < floor {
+                return Err(Error::CovarianceIllConditioned);
+            }
+        }
+        Ok(estimate)
+    }
+}
@@ -0,0 +1,260 @@
+use na::allocator::Allocator;
+use na::{DefaultAllocator, Dim, OMatrix, OVector, RealField};
+use nalgebra as na;
+
+use crate::{
+    compute_kalman_gain, compute_posterior_covariance, compute_residual, CovarianceUpdateMethod,
+    Error, GatingResult, Residual, Result, StateAndCovariance,
+};
+
+/// A nonlinear state transition model, linearized at each step via its
+/// Jacobian.
+///
+/// Unlike [`crate::TransitionModelLinearNoControl`], which requires the
+/// process model to be exactly linear (`x̄ = F x`), this trait allows an
+/// arbitrary `predict` function; the Jacobian of that function at the
+/// current state is used in place of a constant `F` to propagate the
+/// covariance.
+pub trait NonlinearTransitionModel<R, SS>
+where
+    R: RealField,
+    SS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS> + Allocator<R, SS>,
+{
+    /// Predict the state at the next time step given the current state.
+    fn predict(&self, state: &OVector<R, SS>) -> OVector<R, SS>;
+    /// The Jacobian of [`Self::predict`] evaluated at `state`.
+    fn F_jacobian(&self, state: &OVector<R, SS>) -> OMatrix<R, SS, SS>;
+    /// The process noise covariance.
+    fn Q(&self) -> &OMatrix<R, SS, SS>;
+}
+
+/// A nonlinear observation model, linearized at each step via its Jacobian.
+///
+/// Unlike [`crate::ObservationModel`], which requires the measurement model
+/// to be exactly linear (`z = H x`), this trait allows an arbitrary
+/// `predict_observation` function; the Jacobian of that function at the
+/// predicted state is used in place of a constant `H` to compute the
+/// innovation covariance and Kalman gain.
+pub trait NonlinearObservationModel<R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    /// Predict the observation corresponding to a given state.
+    fn predict_observation(&self, state: &OVector<R, SS>) -> OVector<R, OS>;
+    /// The Jacobian of [`Self::predict_observation`] evaluated at `state`.
+    fn H_jacobian(&self, state: &OVector<R, SS>) -> OMatrix<R, OS, SS>;
+    /// The observation noise covariance.
+    fn R(&self) -> &OMatrix<R, OS, OS>;
+}
+
+/// An Extended Kalman Filter (EKF) for a system with nonlinear transition
+/// and/or observation models.
+///
+/// This mirrors [`crate::KalmanFilterNoControl`], but the predict and update
+/// steps linearize the nonlinear [`NonlinearTransitionModel`] and
+/// [`NonlinearObservationModel`] via their Jacobians at each step, rather
+/// than using a fixed `F`/`H` matrix. The covariance update itself reuses
+/// [`CovarianceUpdateMethod`] unchanged.
+pub struct ExtendedKalmanFilterNoControl<'a, R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    transition_model: &'a dyn NonlinearTransitionModel<R, SS>,
+    observation_model: &'a dyn NonlinearObservationModel<R, SS, OS>,
+}
+
+impl<'a, R, SS, OS> ExtendedKalmanFilterNoControl<'a, R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    /// Create a new EKF from a nonlinear transition model and a nonlinear
+    /// observation model.
+    pub fn new(
+        transition_model: &'a dyn NonlinearTransitionModel<R, SS>,
+        observation_model: &'a dyn NonlinearObservationModel<R, SS, OS>,
+    ) -> Self {
+        Self {
+            transition_model,
+            observation_model,
+        }
+    }
+
+    /// Predict the next state and covariance from the previous estimate.
+    fn predict(&self, previous_estimate: &StateAndCovariance<R, SS>) -> StateAndCovariance<R, SS> {
+        let f = self.transition_model.F_jacobian(previous_estimate.state());
+        let state = self.transition_model.predict(previous_estimate.state());
+        let covariance =
+            &f * previous_estimate.covariance() * f.transpose() + self.transition_model.Q();
+        StateAndCovariance::new(state, covariance)
+    }
+
+    /// Compute the observation Jacobian and the residual of an observation
+    /// against a prior estimate.
+    ///
+    /// Both are returned together since `update_with_residual` also needs
+    /// the Jacobian `H` evaluated at this same prior, and `H_jacobian` may
+    /// be expensive to evaluate (e.g. backed by numerical differentiation).
+    fn linearize_and_compute_residual(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+    ) -> (OMatrix<R, OS, SS>, Residual<R, OS>) {
+        let h = self.observation_model.H_jacobian(prior.state());
+        let ht = h.transpose();
+        let innovation = observation - self.observation_model.predict_observation(prior.state());
+        let residual = compute_residual(
+            prior.covariance(),
+            &h,
+            &ht,
+            self.observation_model.R(),
+            innovation,
+        );
+        (h, residual)
+    }
+
+    /// Update a prior (predicted) estimate with an already-linearized
+    /// Jacobian and residual, both evaluated at `prior`.
+    fn update_with_residual(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        h: &OMatrix<R, OS, SS>,
+        residual: &Residual<R, OS>,
+        covariance_update_method: CovarianceUpdateMethod,
+    ) -> Result<StateAndCovariance<R, SS>> {
+        let ht = h.transpose();
+        let p = prior.covariance();
+        let r = self.observation_model.R();
+
+        let (state_correction, kalman_gain) = compute_kalman_gain(p, &ht, residual)?;
+        let state = prior.state() + state_correction;
+        let covariance =
+            compute_posterior_covariance(p, h, r, &kalman_gain, covariance_update_method);
+
+        Ok(StateAndCovariance::new(state, covariance))
+    }
+
+    /// Update a prior (predicted) estimate with an observation.
+    fn update(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        covariance_update_method: CovarianceUpdateMethod,
+    ) -> Result<StateAndCovariance<R, SS>> {
+        let (h, residual) = self.linearize_and_compute_residual(prior, observation);
+        self.update_with_residual(prior, &h, &residual, covariance_update_method)
+    }
+
+    /// Perform one full predict/update step given an observation.
+    ///
+    /// Uses [`CovarianceUpdateMethod::OptimalKalman`] for the covariance
+    /// update, and performs no covariance health check. Use
+    /// [`Self::step_with_options`] to choose a different form or to reject
+    /// ill-conditioned estimates.
+    pub fn step(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        self.step_with_options(
+            previous_estimate,
+            observation,
+            CovarianceUpdateMethod::OptimalKalman,
+            None,
+        )
+    }
+
+    /// Perform one full predict/update step given an observation.
+    ///
+    /// As with [`crate::KalmanFilterNoControl::step_with_options`], an
+    /// observation containing any `NaN` component is treated as missing and
+    /// the predicted (prior) estimate is returned unchanged, and a `Some`
+    /// `rcond_floor` rejects an ill-conditioned resulting estimate with
+    /// [`Error::CovarianceIllConditioned`].
+    pub fn step_with_options(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        covariance_update_method: CovarianceUpdateMethod,
+        rcond_floor: Option<R>,
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        let prior = self.predict(previous_estimate);
+        let estimate = if observation.iter().any(|v| v.clone() != v.clone()) {
+            prior
+        } else {
+            self.update(&prior, observation, covariance_update_method)?
+        };
+        if let Some(floor) = rcond_floor {
+            if estimate.reciprocal_condition() < floor {
+                return Err(Error::CovarianceIllConditioned);
+            }
+        }
+        Ok(estimate)
+    }
+
+    /// Perform one predict/update step, rejecting `observation` if its
+    /// normalized innovation squared exceeds `gate`.
+    ///
+    /// See [`crate::KalmanFilterNoControl::step_with_gating`] for the
+    /// semantics; this mirrors it using the linearized `H` Jacobian in
+    /// place of a constant `H`.
+    pub fn step_with_gating(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        gate: R,
+    ) -> Result<GatingResult<R, SS, OS>> {
+        let prior = self.predict(previous_estimate);
+        let (h, residual) = self.linearize_and_compute_residual(&prior, observation);
+        if residual.normalized_innovation_squared()? > gate {
+            return Ok(GatingResult {
+                estimate: prior,
+                residual,
+                rejected: true,
+            });
+        }
+        let estimate = self.update_with_residual(
+            &prior,
+            &h,
+            &residual,
+            CovarianceUpdateMethod::OptimalKalman,
+        )?;
+        Ok(GatingResult {
+            estimate,
+            residual,
+            rejected: false,
+        })
+    }
+}
@@ -0,0 +1,39 @@
+use core::fmt;
+
+/// Errors that can occur while running the filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The innovation (or prior) covariance could not be inverted.
+    ///
+    /// This typically indicates that the observation noise covariance `R`
+    /// is degenerate or that the filter has diverged.
+    CovarianceNotInvertible,
+    /// The posterior covariance's reciprocal condition number fell below a
+    /// caller-supplied floor.
+    ///
+    /// This typically indicates the filter has diverged, or that the
+    /// covariance has become numerically singular or indefinite. See
+    /// [`crate::StateAndCovariance::reciprocal_condition`].
+    CovarianceIllConditioned,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::CovarianceNotInvertible => {
+                write!(f, "covariance matrix could not be inverted")
+            }
+            Error::CovarianceIllConditioned => {
+                write!(
+                    f,
+                    "covariance matrix's reciprocal condition number fell below the required floor"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A `Result` alias where the error type defaults to [`Error`].
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,665 @@
+//! A Kalman filter and Rauch-Tung-Striebel (RTS) smoother implemented
+//! using [`nalgebra`](https://nalgebra.org) matrices and vectors.
+//!
+//! The filter is generic over the floating point type (`f32`/`f64`, or
+//! anything implementing [`nalgebra::RealField`]) and over the
+//! dimensionality of the state and observation spaces, usually fixed at
+//! compile time via `nalgebra`'s `Const<N>` dimensions. The sensor models
+//! passed to [`KalmanFilterNoControl::step_multi`] are instead dynamically
+//! sized (`Dyn`).
+//!
+//! Two traits describe the physical model being filtered:
+//!
+//! - [`TransitionModelLinearNoControl`] describes how the state evolves
+//!   from one time step to the next (the process model `F`, `Q`).
+//! - [`ObservationModel`] describes how the state maps onto the observed
+//!   quantities (the measurement model `H`, `R`).
+//!
+//! Given implementations of these traits, [`KalmanFilterNoControl::new`]
+//! builds a filter which can be stepped forward one observation at a time
+//! with [`KalmanFilterNoControl::step`], or run over a batch of
+//! observations and smoothed with [`KalmanFilterNoControl::smooth`].
+//!
+//! Matrix accessors (`F`, `Q`, `H`, `R`, ...) follow the usual Kalman
+//! filter notation rather than Rust's snake-case convention.
+#![allow(non_snake_case)]
+
+use na::allocator::Allocator;
+use na::{DefaultAllocator, Dim, OMatrix, OVector, RealField};
+use nalgebra as na;
+
+mod control;
+mod ekf;
+mod error;
+mod state_and_covariance;
+mod time_varying;
+mod ud;
+
+pub use control::{KalmanFilterWithControl, TransitionModelLinearWithControl};
+pub use ekf::{ExtendedKalmanFilterNoControl, NonlinearObservationModel, NonlinearTransitionModel};
+
+pub use error::{Error, Result};
+pub use state_and_covariance::StateAndCovariance;
+pub use time_varying::{KalmanFilterTimeVarying, TransitionModelLinearTimeVarying};
+pub use ud::UdKalmanFilter;
+
+/// How the posterior covariance is computed during the update step.
+///
+/// All three forms are mathematically equivalent in exact arithmetic; they
+/// differ in numerical behavior in the presence of floating point error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CovarianceUpdateMethod {
+    /// The Joseph form, `(I - KH) P (I - KH)^T + K R K^T`.
+    ///
+    /// This is the most numerically robust of the three forms: it remains
+    /// symmetric and positive semi-definite even when the Kalman gain `K`
+    /// is not optimal.
+    JosephForm,
+    /// The textbook optimal-gain form, `(I - KH) P`.
+    ///
+    /// Cheapest to compute, but can drift from symmetric / positive
+    /// semi-definite under accumulated floating point error.
+    OptimalKalman,
+    /// [`CovarianceUpdateMethod::OptimalKalman`], symmetrized afterwards
+    /// via `(P + P^T) / 2`.
+    OptimalKalmanForcedSymmetric,
+}
+
+/// A linear, time-invariant state transition model without a control input.
+///
+/// Implementors describe the process model `x̄ = F x` together with its
+/// process noise covariance `Q`.
+pub trait TransitionModelLinearNoControl<R, SS>
+where
+    R: RealField,
+    SS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS> + Allocator<R, SS>,
+{
+    /// The state transition matrix.
+    fn F(&self) -> &OMatrix<R, SS, SS>;
+    /// The transpose of the state transition matrix.
+    fn FT(&self) -> &OMatrix<R, SS, SS>;
+    /// The process noise covariance.
+    fn Q(&self) -> &OMatrix<R, SS, SS>;
+
+    /// Predict the state at the next time step given the current state.
+    fn predict(&self, state: &OVector<R, SS>) -> OVector<R, SS> {
+        self.F() * state
+    }
+}
+
+/// A linear observation model.
+///
+/// Implementors describe the measurement model `z = H x` together with its
+/// observation noise covariance `R`.
+pub trait ObservationModel<R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    /// The observation matrix.
+    fn H(&self) -> &OMatrix<R, OS, SS>;
+    /// The transpose of the observation matrix.
+    fn HT(&self) -> &OMatrix<R, SS, OS>;
+    /// The observation noise covariance.
+    fn R(&self) -> &OMatrix<R, OS, OS>;
+
+    /// Predict the observation corresponding to a given state.
+    fn predict_observation(&self, state: &OVector<R, SS>) -> OVector<R, OS> {
+        self.H() * state
+    }
+}
+
+/// The measurement residual ("innovation") and its covariance from a single
+/// predict/update step: `ν = z - H x̄` and `S = H P Hᵀ + R`.
+///
+/// Exposed by [`KalmanFilterNoControl::step_with_gating`] and
+/// [`ExtendedKalmanFilterNoControl::step_with_gating`] so that callers can
+/// inspect or gate on the residual directly, in addition to the built-in
+/// chi-square test those methods perform via
+/// [`Self::normalized_innovation_squared`](Residual::normalized_innovation_squared).
+#[derive(Debug, Clone)]
+pub struct Residual<R, OS>
+where
+    R: RealField,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, OS, OS> + Allocator<R, OS>,
+{
+    innovation: OVector<R, OS>,
+    innovation_covariance: OMatrix<R, OS, OS>,
+}
+
+impl<R, OS> Residual<R, OS>
+where
+    R: RealField,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, OS, OS> + Allocator<R, OS>,
+{
+    /// The innovation (measurement residual), `ν = z - H x̄`.
+    pub fn innovation(&self) -> &OVector<R, OS> {
+        &self.innovation
+    }
+
+    /// The innovation covariance, `S = H P Hᵀ + R`.
+    pub fn innovation_covariance(&self) -> &OMatrix<R, OS, OS> {
+        &self.innovation_covariance
+    }
+
+    /// The normalized innovation squared, `νᵀ S⁻¹ ν`.
+    ///
+    /// Under the filter's modeling assumptions this statistic is
+    /// chi-square distributed with `OS` degrees of freedom. Comparing it
+    /// against a chi-square quantile is the basis of the gating performed
+    /// by `step_with_gating`.
+    pub fn normalized_innovation_squared(&self) -> Result<R> {
+        let innovation_covariance_inv = self
+            .innovation_covariance
+            .clone()
+            .try_inverse()
+            .ok_or(Error::CovarianceNotInvertible)?;
+        let weighted_innovation = innovation_covariance_inv * &self.innovation;
+        Ok(self.innovation.dot(&weighted_innovation))
+    }
+}
+
+/// Compute the measurement residual and its covariance for a prior estimate.
+///
+/// Shared between [`KalmanFilterNoControl`] and [`ExtendedKalmanFilterNoControl`],
+/// differing only in how `h` (linear `H` or a Jacobian) is obtained.
+pub(crate) fn compute_residual<R, SS, OS>(
+    p: &OMatrix<R, SS, SS>,
+    h: &OMatrix<R, OS, SS>,
+    ht: &OMatrix<R, SS, OS>,
+    r: &OMatrix<R, OS, OS>,
+    innovation: OVector<R, OS>,
+) -> Residual<R, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, OS>,
+{
+    let innovation_covariance = h * p * ht + r;
+    Residual {
+        innovation,
+        innovation_covariance,
+    }
+}
+
+/// Compute the Kalman gain and the resulting state correction `K * innovation`
+/// from an already-computed [`Residual`].
+///
+/// Shared between [`KalmanFilterNoControl`] and [`ExtendedKalmanFilterNoControl`]
+/// so that both filters compute the gain identically, differing only in how
+/// `ht` (linear `H` or a Jacobian) is obtained.
+#[allow(clippy::type_complexity)]
+pub(crate) fn compute_kalman_gain<R, SS, OS>(
+    p: &OMatrix<R, SS, SS>,
+    ht: &OMatrix<R, SS, OS>,
+    residual: &Residual<R, OS>,
+) -> Result<(OVector<R, SS>, OMatrix<R, SS, OS>)>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    let innovation_covariance_inv = residual
+        .innovation_covariance
+        .clone()
+        .try_inverse()
+        .ok_or(Error::CovarianceNotInvertible)?;
+    let kalman_gain = p * ht * innovation_covariance_inv;
+    let state_correction = &kalman_gain * residual.innovation.clone();
+    Ok((state_correction, kalman_gain))
+}
+
+/// The result of gated filtering via `step_with_gating`: the resulting
+/// estimate together with the residual used to accept or reject the
+/// observation.
+#[derive(Debug, Clone)]
+pub struct GatingResult<R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator:
+        Allocator<R, SS, SS> + Allocator<R, SS> + Allocator<R, OS, OS> + Allocator<R, OS>,
+{
+    estimate: StateAndCovariance<R, SS>,
+    residual: Residual<R, OS>,
+    rejected: bool,
+}
+
+impl<R, SS, OS> GatingResult<R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator:
+        Allocator<R, SS, SS> + Allocator<R, SS> + Allocator<R, OS, OS> + Allocator<R, OS>,
+{
+    /// The resulting estimate: the posterior if the observation was
+    /// accepted, or the unchanged prior if it was rejected by the gate.
+    pub fn estimate(&self) -> &StateAndCovariance<R, SS> {
+        &self.estimate
+    }
+
+    /// The residual the gating decision was based on.
+    pub fn residual(&self) -> &Residual<R, OS> {
+        &self.residual
+    }
+
+    /// Whether the observation was rejected by the gate.
+    pub fn rejected(&self) -> bool {
+        self.rejected
+    }
+}
+
+/// Compute the posterior covariance for a given [`CovarianceUpdateMethod`].
+///
+/// Shared between [`KalmanFilterNoControl`] and [`ExtendedKalmanFilterNoControl`].
+pub(crate) fn compute_posterior_covariance<R, SS, OS>(
+    p: &OMatrix<R, SS, SS>,
+    h: &OMatrix<R, OS, SS>,
+    r: &OMatrix<R, OS, OS>,
+    kalman_gain: &OMatrix<R, SS, OS>,
+    covariance_update_method: CovarianceUpdateMethod,
+) -> OMatrix<R, SS, SS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    match covariance_update_method {
+        CovarianceUpdateMethod::OptimalKalman => p - kalman_gain * h * p,
+        CovarianceUpdateMethod::OptimalKalmanForcedSymmetric => {
+            let covariance = p - kalman_gain * h * p;
+            let half: R = na::convert(0.5);
+            (&covariance + covariance.transpose()) * half
+        }
+        CovarianceUpdateMethod::JosephForm => {
+            let (nrows, ncols) = p.shape_generic();
+            let identity = OMatrix::<R, SS, SS>::identity_generic(nrows, ncols);
+            let i_minus_kh = &identity - kalman_gain * h;
+            &i_minus_kh * p * i_minus_kh.transpose() + kalman_gain * r * kalman_gain.transpose()
+        }
+    }
+}
+
+/// A single sensor's dynamically-sized observation model paired with its
+/// observation for this step, or `None` if the sensor has nothing new to
+/// report. Used by [`KalmanFilterNoControl::step_multi`] to fuse sensors of
+/// differing dimension within a single slice.
+pub type SensorUpdate<'a, R, SS> = (
+    &'a dyn ObservationModel<R, SS, na::Dyn>,
+    Option<OVector<R, na::Dyn>>,
+);
+
+/// A Kalman filter for a linear, time-invariant system with no control input.
+///
+/// Borrows its transition and observation models for the duration of its
+/// use, so that the same models can be reused across many filter runs.
+pub struct KalmanFilterNoControl<'a, R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    transition_model: &'a dyn TransitionModelLinearNoControl<R, SS>,
+    observation_model: &'a dyn ObservationModel<R, SS, OS>,
+}
+
+impl<'a, R, SS, OS> KalmanFilterNoControl<'a, R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    /// Create a new filter from a transition model and an observation model.
+    pub fn new(
+        transition_model: &'a dyn TransitionModelLinearNoControl<R, SS>,
+        observation_model: &'a dyn ObservationModel<R, SS, OS>,
+    ) -> Self {
+        Self {
+            transition_model,
+            observation_model,
+        }
+    }
+
+    /// Predict the next state and covariance from the previous estimate.
+    fn predict(&self, previous_estimate: &StateAndCovariance<R, SS>) -> StateAndCovariance<R, SS> {
+        let f = self.transition_model.F();
+        let state = self.transition_model.predict(previous_estimate.state());
+        let covariance = f * previous_estimate.covariance() * self.transition_model.FT()
+            + self.transition_model.Q();
+        StateAndCovariance::new(state, covariance)
+    }
+
+    /// Compute the residual of an observation against a prior estimate.
+    fn residual(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+    ) -> Residual<R, OS> {
+        let innovation = observation - self.observation_model.predict_observation(prior.state());
+        compute_residual(
+            prior.covariance(),
+            self.observation_model.H(),
+            self.observation_model.HT(),
+            self.observation_model.R(),
+            innovation,
+        )
+    }
+
+    /// Update a prior (predicted) estimate with an already-computed residual.
+    fn update_with_residual(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        residual: &Residual<R, OS>,
+        covariance_update_method: CovarianceUpdateMethod,
+    ) -> Result<StateAndCovariance<R, SS>> {
+        let h = self.observation_model.H();
+        let ht = self.observation_model.HT();
+        let p = prior.covariance();
+        let r = self.observation_model.R();
+
+        let (state_correction, kalman_gain) = compute_kalman_gain(p, ht, residual)?;
+        let state = prior.state() + state_correction;
+        let covariance =
+            compute_posterior_covariance(p, h, r, &kalman_gain, covariance_update_method);
+
+        Ok(StateAndCovariance::new(state, covariance))
+    }
+
+    /// Update a prior (predicted) estimate with an observation.
+    fn update(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        covariance_update_method: CovarianceUpdateMethod,
+    ) -> Result<StateAndCovariance<R, SS>> {
+        let residual = self.residual(prior, observation);
+        self.update_with_residual(prior, &residual, covariance_update_method)
+    }
+
+    /// Perform one full predict/update step given an observation.
+    ///
+    /// Uses [`CovarianceUpdateMethod::OptimalKalman`] for the covariance
+    /// update, and performs no covariance health check. Use
+    /// [`Self::step_with_options`] to choose a different form or to reject
+    /// ill-conditioned estimates.
+    pub fn step(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        self.step_with_options(
+            previous_estimate,
+            observation,
+            CovarianceUpdateMethod::OptimalKalman,
+            None,
+        )
+    }
+
+    /// Perform one full predict/update step given an observation.
+    ///
+    /// If `observation` contains any `NaN` component, the observation is
+    /// treated as missing: the predicted (prior) estimate is returned
+    /// unchanged rather than attempting an update. This allows [`Self::smooth`]
+    /// to work transparently over a stream of observations with gaps.
+    ///
+    /// If `rcond_floor` is `Some`, the resulting estimate's
+    /// [`StateAndCovariance::reciprocal_condition`] is checked against it;
+    /// falling below the floor returns [`Error::CovarianceIllConditioned`]
+    /// instead of the estimate, giving early detection of filter divergence
+    /// rather than silently propagating a broken covariance. `None` skips
+    /// the check, as in [`Self::step`].
+    pub fn step_with_options(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        covariance_update_method: CovarianceUpdateMethod,
+        rcond_floor: Option<R>,
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        let prior = self.predict(previous_estimate);
+        let estimate = if observation.iter().any(|v| v.clone() != v.clone()) {
+            prior
+        } else {
+            self.update(&prior, observation, covariance_update_method)?
+        };
+        if let Some(floor) = rcond_floor {
+            if estimate.reciprocal_condition() < floor {
+                return Err(Error::CovarianceIllConditioned);
+            }
+        }
+        Ok(estimate)
+    }
+
+    /// Perform one predict/update step, rejecting `observation` if its
+    /// normalized innovation squared exceeds `gate`.
+    ///
+    /// `gate` is typically a chi-square quantile for `OS` degrees of
+    /// freedom (e.g. the 99th percentile) chosen by the caller. A rejected
+    /// observation is treated like a missing one in [`Self::step_with_options`]:
+    /// the predicted (prior) estimate is returned unchanged, while
+    /// [`GatingResult::rejected`] reports the rejection and
+    /// [`GatingResult::residual`] exposes the residual that triggered it.
+    /// This gives robust tracking against spurious detections, complementing
+    /// the `NaN`-based handling of missing detections.
+    ///
+    /// Uses [`CovarianceUpdateMethod::OptimalKalman`] for the covariance
+    /// update when the observation is accepted.
+    pub fn step_with_gating(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        gate: R,
+    ) -> Result<GatingResult<R, SS, OS>> {
+        let prior = self.predict(previous_estimate);
+        let residual = self.residual(&prior, observation);
+        if residual.normalized_innovation_squared()? > gate {
+            return Ok(GatingResult {
+                estimate: prior,
+                residual,
+                rejected: true,
+            });
+        }
+        let estimate =
+            self.update_with_residual(&prior, &residual, CovarianceUpdateMethod::OptimalKalman)?;
+        Ok(GatingResult {
+            estimate,
+            residual,
+            rejected: false,
+        })
+    }
+
+    /// Perform one predict/update step, fusing several heterogeneous sensor
+    /// updates sequentially after a single predict step.
+    ///
+    /// `updates` pairs each sensor's [`ObservationModel`] with its
+    /// observation for this step, or `None` if that sensor has nothing new
+    /// to report. Each model is dynamically sized (`OS` = [`na::Dyn`]) so
+    /// that sensors of differing dimension (e.g. a 1D barometer alongside a
+    /// 3D GPS fix) can share a single slice. The predict step runs once;
+    /// each present observation is then applied in turn as an independent
+    /// measurement update against the estimate left by the previous one, so
+    /// later sensors benefit from the covariance reduction of earlier ones
+    /// within the same step.
+    ///
+    /// Uses [`CovarianceUpdateMethod::OptimalKalman`] for the covariance
+    /// update, and performs no covariance health check. Use
+    /// [`Self::step_multi_with_options`] to choose a different form or to
+    /// reject ill-conditioned estimates.
+    pub fn step_multi(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        updates: &[SensorUpdate<R, SS>],
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        DefaultAllocator: Allocator<R, na::Dyn, na::Dyn>
+            + Allocator<R, na::Dyn, SS>
+            + Allocator<R, SS, na::Dyn>
+            + Allocator<R, na::Dyn>,
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        self.step_multi_with_options(
+            previous_estimate,
+            updates,
+            CovarianceUpdateMethod::OptimalKalman,
+            None,
+        )
+    }
+
+    /// Perform one predict/update step, fusing several heterogeneous sensor
+    /// updates sequentially after a single predict step.
+    ///
+    /// See [`Self::step_multi`] for the semantics of `updates`. As with
+    /// [`Self::step_with_options`], a `Some` `rcond_floor` rejects an
+    /// ill-conditioned resulting estimate with
+    /// [`Error::CovarianceIllConditioned`].
+    pub fn step_multi_with_options(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        updates: &[SensorUpdate<R, SS>],
+        covariance_update_method: CovarianceUpdateMethod,
+        rcond_floor: Option<R>,
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        DefaultAllocator: Allocator<R, na::Dyn, na::Dyn>
+            + Allocator<R, na::Dyn, SS>
+            + Allocator<R, SS, na::Dyn>
+            + Allocator<R, na::Dyn>,
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        let mut estimate = self.predict(previous_estimate);
+        for (observation_model, observation) in updates {
+            let Some(observation) = observation else {
+                continue;
+            };
+            let innovation = observation - observation_model.predict_observation(estimate.state());
+            let residual = compute_residual(
+                estimate.covariance(),
+                observation_model.H(),
+                observation_model.HT(),
+                observation_model.R(),
+                innovation,
+            );
+            let (state_correction, kalman_gain) =
+                compute_kalman_gain(estimate.covariance(), observation_model.HT(), &residual)?;
+            let state = estimate.state() + state_correction;
+            let covariance = compute_posterior_covariance(
+                estimate.covariance(),
+                observation_model.H(),
+                observation_model.R(),
+                &kalman_gain,
+                covariance_update_method,
+            );
+            estimate = StateAndCovariance::new(state, covariance);
+        }
+        if let Some(floor) = rcond_floor {
+            if estimate.reciprocal_condition() < floor {
+                return Err(Error::CovarianceIllConditioned);
+            }
+        }
+        Ok(estimate)
+    }
+
+    /// Filter and then smooth a batch of observations with an RTS smoother.
+    ///
+    /// Observations containing `NaN` components are treated as missing, as
+    /// described in [`Self::step_with_options`].
+    pub fn smooth(
+        &self,
+        initial_estimate: &StateAndCovariance<R, SS>,
+        observations: &[OVector<R, OS>],
+    ) -> Result<Vec<StateAndCovariance<R, SS>>> {
+        if observations.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Forward pass: keep both the prior (predicted) and posterior
+        // (updated) estimate at every step, as the backward pass needs both.
+        let mut priors = Vec::with_capacity(observations.len());
+        let mut posteriors = Vec::with_capacity(observations.len());
+
+        let mut previous = initial_estimate.clone();
+        for observation in observations {
+            let prior = self.predict(&previous);
+            let posterior = if observation.iter().any(|v| v.clone() != v.clone()) {
+                prior.clone()
+            } else {
+                self.update(&prior, observation, CovarianceUpdateMethod::OptimalKalman)?
+            };
+            priors.push(prior);
+            posteriors.push(posterior.clone());
+            previous = posterior;
+        }
+
+        // Backward RTS pass.
+        let n = observations.len();
+        let mut smoothed = posteriors.clone();
+        for k in (0..n - 1).rev() {
+            let p_posterior = posteriors[k].covariance();
+            let p_prior_next = priors[k + 1].covariance();
+            let p_prior_next_inv = p_prior_next
+                .clone()
+                .try_inverse()
+                .ok_or(Error::CovarianceNotInvertible)?;
+            let c = p_posterior * self.transition_model.FT() * p_prior_next_inv;
+
+            let state =
+                posteriors[k].state() + &c * (smoothed[k + 1].state() - priors[k + 1].state());
+            let covariance =
+                p_posterior + &c * (smoothed[k + 1].covariance() - p_prior_next) * c.transpose();
+
+            smoothed[k] = StateAndCovariance::new(state, covariance);
+        }
+
+        Ok(smoothed)
+    }
+}
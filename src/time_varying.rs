@@ -0,0 +1,198 @@
+use na::allocator::Allocator;
+use na::{DefaultAllocator, Dim, OMatrix, OVector, RealField};
+use nalgebra as na;
+
+use crate::{
+    compute_kalman_gain, compute_posterior_covariance, compute_residual, CovarianceUpdateMethod,
+    Error, ObservationModel, Residual, Result, StateAndCovariance,
+};
+
+/// A linear state transition model whose matrices vary with the elapsed
+/// time step `dt`, rather than being fixed at construction.
+///
+/// Unlike [`crate::TransitionModelLinearNoControl`], which assumes a single
+/// fixed `dt` baked into `F` and `Q` once, this trait recomputes them from
+/// `dt` on every call. This supports irregular or asynchronous measurement
+/// timing, and in particular State Noise Compensation (SNC), where `Q(dt)`
+/// is rebuilt each step from a continuous-time acceleration power spectral
+/// density rather than a `Q` precomputed for one fixed `dt`.
+pub trait TransitionModelLinearTimeVarying<R, SS>
+where
+    R: RealField,
+    SS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS> + Allocator<R, SS>,
+{
+    /// The state transition matrix for a step of length `dt`.
+    fn F(&self, dt: R) -> OMatrix<R, SS, SS>;
+    /// The transpose of the state transition matrix for a step of length `dt`.
+    fn FT(&self, dt: R) -> OMatrix<R, SS, SS>;
+    /// The process noise covariance accumulated over a step of length `dt`.
+    fn Q(&self, dt: R) -> OMatrix<R, SS, SS>;
+
+    /// Predict the state after a step of length `dt`.
+    fn predict(&self, state: &OVector<R, SS>, dt: R) -> OVector<R, SS> {
+        self.F(dt) * state
+    }
+}
+
+/// A Kalman filter for a linear system whose transition model varies with
+/// the elapsed time step `dt`.
+///
+/// This mirrors [`crate::KalmanFilterNoControl`], except every step takes
+/// an explicit `dt` and queries [`TransitionModelLinearTimeVarying`] for the
+/// `F(dt)`/`Q(dt)` appropriate to that step, rather than using matrices
+/// fixed at construction. The observation side and the
+/// [`CovarianceUpdateMethod`] choices are unchanged.
+pub struct KalmanFilterTimeVarying<'a, R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    transition_model: &'a dyn TransitionModelLinearTimeVarying<R, SS>,
+    observation_model: &'a dyn ObservationModel<R, SS, OS>,
+}
+
+impl<'a, R, SS, OS> KalmanFilterTimeVarying<'a, R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    /// Create a new filter from a time-varying transition model and an
+    /// observation model.
+    pub fn new(
+        transition_model: &'a dyn TransitionModelLinearTimeVarying<R, SS>,
+        observation_model: &'a dyn ObservationModel<R, SS, OS>,
+    ) -> Self {
+        Self {
+            transition_model,
+            observation_model,
+        }
+    }
+
+    /// Predict the next state and covariance from the previous estimate,
+    /// given the elapsed time step `dt`.
+    fn predict(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        dt: R,
+    ) -> StateAndCovariance<R, SS> {
+        let f = self.transition_model.F(dt.clone());
+        let state = self
+            .transition_model
+            .predict(previous_estimate.state(), dt.clone());
+        let covariance = &f * previous_estimate.covariance() * self.transition_model.FT(dt.clone())
+            + self.transition_model.Q(dt);
+        StateAndCovariance::new(state, covariance)
+    }
+
+    /// Compute the residual of an observation against a prior estimate.
+    fn residual(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+    ) -> Residual<R, OS> {
+        let innovation = observation - self.observation_model.predict_observation(prior.state());
+        compute_residual(
+            prior.covariance(),
+            self.observation_model.H(),
+            self.observation_model.HT(),
+            self.observation_model.R(),
+            innovation,
+        )
+    }
+
+    /// Update a prior (predicted) estimate with an observation.
+    fn update(
+        &self,
+        prior: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        covariance_update_method: CovarianceUpdateMethod,
+    ) -> Result<StateAndCovariance<R, SS>> {
+        let residual = self.residual(prior, observation);
+        let h = self.observation_model.H();
+        let ht = self.observation_model.HT();
+        let p = prior.covariance();
+        let r = self.observation_model.R();
+
+        let (state_correction, kalman_gain) = compute_kalman_gain(p, ht, &residual)?;
+        let state = prior.state() + state_correction;
+        let covariance =
+            compute_posterior_covariance(p, h, r, &kalman_gain, covariance_update_method);
+
+        Ok(StateAndCovariance::new(state, covariance))
+    }
+
+    /// Perform one full predict/update step given an observation and the
+    /// elapsed time step `dt` since `previous_estimate`.
+    ///
+    /// Uses [`CovarianceUpdateMethod::OptimalKalman`] for the covariance
+    /// update, and performs no covariance health check. Use
+    /// [`Self::step_with_dt_options`] to choose a different form or to
+    /// reject ill-conditioned estimates.
+    pub fn step_with_dt(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        dt: R,
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        self.step_with_dt_options(
+            previous_estimate,
+            observation,
+            dt,
+            CovarianceUpdateMethod::OptimalKalman,
+            None,
+        )
+    }
+
+    /// Perform one full predict/update step given an observation and the
+    /// elapsed time step `dt` since `previous_estimate`.
+    ///
+    /// As with [`crate::KalmanFilterNoControl::step_with_options`], an
+    /// observation containing any `NaN` component is treated as missing and
+    /// the predicted (prior) estimate is returned unchanged, and a `Some`
+    /// `rcond_floor` rejects an ill-conditioned resulting estimate with
+    /// [`Error::CovarianceIllConditioned`].
+    pub fn step_with_dt_options(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+        dt: R,
+        covariance_update_method: CovarianceUpdateMethod,
+        rcond_floor: Option<R>,
+    ) -> Result<StateAndCovariance<R, SS>>
+    where
+        SS: na::DimSub<na::U1>,
+        DefaultAllocator: Allocator<R, na::DimDiff<SS, na::U1>>,
+    {
+        let prior = self.predict(previous_estimate, dt);
+        let estimate = if observation.iter().any(|v| v.clone() != v.clone()) {
+            prior
+        } else {
+            self.update(&prior, observation, covariance_update_method)?
+        };
+        if let Some(floor) = rcond_floor {
+            if estimate.reciprocal_condition() < floor {
+                return Err(Error::CovarianceIllConditioned);
+            }
+        }
+        Ok(estimate)
+    }
+}
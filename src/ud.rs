@@ -0,0 +1,302 @@
+use na::allocator::Allocator;
+use na::{DefaultAllocator, Dim, OMatrix, OVector, RealField};
+use nalgebra as na;
+
+use crate::{Error, ObservationModel, Result, StateAndCovariance, TransitionModelLinearNoControl};
+
+/// Decompose a symmetric positive semi-definite matrix into `P = U D Uᵀ`,
+/// a unit upper-triangular `U` and a diagonal `D`, via the standard
+/// Bierman in-place UD factorization.
+fn udu_decompose<R, SS>(p: &OMatrix<R, SS, SS>) -> (OMatrix<R, SS, SS>, OVector<R, SS>)
+where
+    R: RealField,
+    SS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS> + Allocator<R, SS>,
+{
+    let zero: R = na::convert(0.0);
+    let n = p.nrows();
+    let (nrows, ncols) = p.shape_generic();
+    let mut u = OMatrix::<R, SS, SS>::identity_generic(nrows, ncols);
+    let mut d = OVector::<R, SS>::zeros_generic(nrows, na::Const::<1>);
+    let mut work = p.clone();
+
+    for j in (1..n).rev() {
+        let dj = work[(j, j)].clone();
+        d[j] = dj.clone();
+        let alpha = if dj > zero.clone() {
+            na::convert::<f64, R>(1.0) / dj
+        } else {
+            zero.clone()
+        };
+        for k in 0..j {
+            let beta = work[(k, j)].clone();
+            let ukj = alpha.clone() * beta.clone();
+            u[(k, j)] = ukj;
+            for i in 0..=k {
+                let uij = u[(i, j)].clone();
+                work[(i, k)] -= beta.clone() * uij;
+            }
+        }
+    }
+    d[0] = work[(0, 0)].clone();
+
+    (u, d)
+}
+
+/// Reconstruct `P = U D Uᵀ` from its UD factorization.
+fn reconstruct_covariance<R, SS>(u: &OMatrix<R, SS, SS>, d: &OVector<R, SS>) -> OMatrix<R, SS, SS>
+where
+    R: RealField,
+    SS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS> + Allocator<R, SS>,
+{
+    let d_mat = OMatrix::<R, SS, SS>::from_diagonal(d);
+    u * d_mat * u.transpose()
+}
+
+/// Propagate a UD-factored covariance `U D Uᵀ` through the linear
+/// transition `F`, adding process noise `Q`, via Thornton's modified
+/// weighted Gram-Schmidt (MWGS) algorithm.
+///
+/// Only the diagonal of `Q` is used. This is the standard restriction of
+/// the classical Bierman/Thornton UD formulation; correlated process noise
+/// is not supported.
+fn thornton_time_update<R, SS>(
+    u: &OMatrix<R, SS, SS>,
+    d: &OVector<R, SS>,
+    f: &OMatrix<R, SS, SS>,
+    q: &OMatrix<R, SS, SS>,
+) -> (OMatrix<R, SS, SS>, OVector<R, SS>)
+where
+    R: RealField,
+    SS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS> + Allocator<R, SS>,
+{
+    let zero: R = na::convert(0.0);
+    let n = u.nrows();
+    let g = f * u;
+
+    // Augmented weighted matrix: n rows, 2n columns. The first n columns
+    // are `G = F U`, weighted by `d`; the noise enters directly in state
+    // coordinates via the identity, weighted by `q`.
+    let mut w: Vec<Vec<R>> = (0..n)
+        .map(|i| {
+            let mut row: Vec<R> = (0..n).map(|k| g[(i, k)].clone()).collect();
+            row.extend((0..n).map(|k| {
+                if i == k {
+                    na::convert(1.0)
+                } else {
+                    zero.clone()
+                }
+            }));
+            row
+        })
+        .collect();
+    let mut weights: Vec<R> = (0..n).map(|k| d[k].clone()).collect();
+    weights.extend((0..n).map(|k| q[(k, k)].clone()));
+
+    let (nrows, ncols) = u.shape_generic();
+    let mut u_new = OMatrix::<R, SS, SS>::identity_generic(nrows, ncols);
+    let mut d_new = OVector::<R, SS>::zeros_generic(nrows, na::Const::<1>);
+
+    for j in (0..n).rev() {
+        let sigma: R = w[j]
+            .iter()
+            .zip(weights.iter())
+            .map(|(wjk, wt)| wjk.clone() * wjk.clone() * wt.clone())
+            .fold(zero.clone(), |acc, term| acc + term);
+        d_new[j] = sigma.clone();
+
+        for i in 0..j {
+            let sigma_i: R = w[i]
+                .iter()
+                .zip(w[j].iter())
+                .zip(weights.iter())
+                .map(|((wik, wjk), wt)| wik.clone() * wjk.clone() * wt.clone())
+                .fold(zero.clone(), |acc, term| acc + term);
+            let u_ij = if sigma > zero.clone() {
+                sigma_i / sigma.clone()
+            } else {
+                zero.clone()
+            };
+            u_new[(i, j)] = u_ij.clone();
+
+            let (head, tail) = w.split_at_mut(j);
+            let wi = &mut head[i];
+            let wj = &tail[0];
+            for (wi_k, wj_k) in wi.iter_mut().zip(wj.iter()) {
+                *wi_k -= u_ij.clone() * wj_k.clone();
+            }
+        }
+    }
+
+    (u_new, d_new)
+}
+
+/// Apply one scalar measurement update (Bierman's rank-one UD update) to a
+/// UD-factored covariance and its corresponding state estimate, in place.
+///
+/// `a` is the measurement row, so the scalar observation is modeled as
+/// `a · x + noise` with noise variance `r`; `residual` is the
+/// corresponding scalar innovation `z - a · x`.
+fn bierman_scalar_update<R, SS>(
+    u: &mut OMatrix<R, SS, SS>,
+    d: &mut OVector<R, SS>,
+    state: &mut OVector<R, SS>,
+    a: &OVector<R, SS>,
+    r: R,
+    residual: R,
+) -> Result<()>
+where
+    R: RealField,
+    SS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS> + Allocator<R, SS>,
+{
+    let zero: R = na::convert(0.0);
+    let n = u.nrows();
+
+    let f: Vec<R> = (0..n)
+        .map(|k| {
+            let mut acc = zero.clone();
+            for i in 0..=k {
+                acc += u[(i, k)].clone() * a[i].clone();
+            }
+            acc
+        })
+        .collect();
+    let v: Vec<R> = (0..n).map(|k| d[k].clone() * f[k].clone()).collect();
+
+    let mut alpha = r.clone() + v[0].clone() * f[0].clone();
+    if alpha <= zero {
+        return Err(Error::CovarianceNotInvertible);
+    }
+    let d0_old = d[0].clone();
+    d[0] = d0_old * r / alpha.clone();
+    let mut b: Vec<R> = vec![zero.clone(); n];
+    b[0] = v[0].clone();
+
+    for j in 1..n {
+        let alpha_prev = alpha.clone();
+        alpha = alpha_prev.clone() + v[j].clone() * f[j].clone();
+        if alpha <= zero {
+            return Err(Error::CovarianceNotInvertible);
+        }
+        let dj_old = d[j].clone();
+        d[j] = dj_old * alpha_prev.clone() / alpha.clone();
+        let lambda = -f[j].clone() / alpha_prev;
+        for i in 0..j {
+            let b_i_before = b[i].clone();
+            let u_ij_old = u[(i, j)].clone();
+            u[(i, j)] = u_ij_old.clone() + b_i_before.clone() * lambda.clone();
+            b[i] = b_i_before + u_ij_old * v[j].clone();
+        }
+        b[j] = v[j].clone();
+    }
+
+    for i in 0..n {
+        state[i] += b[i].clone() / alpha.clone() * residual.clone();
+    }
+
+    Ok(())
+}
+
+/// A Kalman filter for a linear, time-invariant system with no control
+/// input, using a Bierman/Thornton UD (square-root) covariance
+/// representation internally for numerical robustness under poor
+/// conditioning or high process noise.
+///
+/// `P = U D Uᵀ` is factored as a unit-upper-triangular `U` and a diagonal
+/// `D`. The time update propagates `U`/`D` through `F` via Thornton's
+/// modified weighted Gram-Schmidt algorithm; the observation update applies
+/// one scalar component of the observation at a time via Bierman's rank-one
+/// update, avoiding ever forming or inverting the dense innovation
+/// covariance. [`Self::step`] reconstructs `P` on return so that the
+/// result is a plain [`StateAndCovariance`], interchangeable with
+/// [`crate::KalmanFilterNoControl`].
+///
+/// This assumes the process noise `Q` and observation noise `R` are
+/// diagonal, as required by the classical Bierman/Thornton formulation;
+/// any off-diagonal entries are ignored.
+pub struct UdKalmanFilter<'a, R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    transition_model: &'a dyn TransitionModelLinearNoControl<R, SS>,
+    observation_model: &'a dyn ObservationModel<R, SS, OS>,
+}
+
+impl<'a, R, SS, OS> UdKalmanFilter<'a, R, SS, OS>
+where
+    R: RealField,
+    SS: Dim,
+    OS: Dim,
+    DefaultAllocator: Allocator<R, SS, SS>
+        + Allocator<R, SS, OS>
+        + Allocator<R, OS, SS>
+        + Allocator<R, OS, OS>
+        + Allocator<R, SS>
+        + Allocator<R, OS>,
+{
+    /// Create a new UD filter from a transition model and an observation
+    /// model.
+    pub fn new(
+        transition_model: &'a dyn TransitionModelLinearNoControl<R, SS>,
+        observation_model: &'a dyn ObservationModel<R, SS, OS>,
+    ) -> Self {
+        Self {
+            transition_model,
+            observation_model,
+        }
+    }
+
+    /// Perform one full predict/update step given an observation.
+    ///
+    /// If `observation` contains any `NaN` component, the observation is
+    /// treated as missing, matching
+    /// [`crate::KalmanFilterNoControl::step_with_options`].
+    pub fn step(
+        &self,
+        previous_estimate: &StateAndCovariance<R, SS>,
+        observation: &OVector<R, OS>,
+    ) -> Result<StateAndCovariance<R, SS>> {
+        let (u, d) = udu_decompose(previous_estimate.covariance());
+        let (mut u, mut d) =
+            thornton_time_update(&u, &d, self.transition_model.F(), self.transition_model.Q());
+        let mut state = self.transition_model.predict(previous_estimate.state());
+
+        if observation.iter().any(|v| v.clone() != v.clone()) {
+            let covariance = reconstruct_covariance(&u, &d);
+            return Ok(StateAndCovariance::new(state, covariance));
+        }
+
+        let h = self.observation_model.H();
+        let r = self.observation_model.R();
+        let (state_nrows, _) = state.shape_generic();
+        for row in 0..h.nrows() {
+            let mut a = OVector::<R, SS>::zeros_generic(state_nrows, na::Const::<1>);
+            for i in 0..h.ncols() {
+                a[i] = h[(row, i)].clone();
+            }
+            // Bierman's scalar update processes one observation component at
+            // a time, folding each into `state` before the next; the
+            // predicted observation is therefore recomputed against the
+            // partially-updated state so the sequential result matches the
+            // batch update `KalmanFilterNoControl` would produce.
+            let predicted = self.observation_model.predict_observation(&state)[row].clone();
+            let residual = observation[row].clone() - predicted;
+            let r_scalar = r[(row, row)].clone();
+            bierman_scalar_update(&mut u, &mut d, &mut state, &a, r_scalar, residual)?;
+        }
+
+        let covariance = reconstruct_covariance(&u, &d);
+        Ok(StateAndCovariance::new(state, covariance))
+    }
+}
@@ -1,7 +1,7 @@
 use approx::assert_relative_eq;
 use na::allocator::Allocator;
 use na::dimension::DimMin;
-use na::dimension::{U2, U4};
+use na::dimension::{Dyn, U2, U4};
 use na::OMatrix;
 use na::{DefaultAllocator, RealField};
 use nalgebra as na;
@@ -9,7 +9,10 @@ use nalgebra::{Const, OVector};
 use serde::{Deserialize, Serialize};
 
 use adskalman::{
-    CovarianceUpdateMethod, KalmanFilterNoControl, ObservationModel, TransitionModelLinearNoControl,
+    CovarianceUpdateMethod, Error, ExtendedKalmanFilterNoControl, KalmanFilterNoControl,
+    KalmanFilterTimeVarying, KalmanFilterWithControl, NonlinearObservationModel,
+    NonlinearTransitionModel, ObservationModel, TransitionModelLinearNoControl,
+    TransitionModelLinearTimeVarying, TransitionModelLinearWithControl, UdKalmanFilter,
 };
 
 // This data was generated by running `online_tracking.rs` in `examples`.
@@ -156,6 +159,335 @@ where
     }
 }
 
+// Both motion and observation models above are already linear, so their
+// nonlinear Jacobians are simply their constant matrices. This lets us
+// check that the EKF reduces exactly to the linear KF on a linear model.
+impl<R> NonlinearTransitionModel<R, U4> for ConstantVelocity2DModel<R>
+where
+    R: RealField,
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U2, U4>,
+    DefaultAllocator: Allocator<R, U4, U2>,
+    DefaultAllocator: Allocator<R, U2, U2>,
+    DefaultAllocator: Allocator<R, U4>,
+{
+    fn predict(&self, state: &OVector<R, U4>) -> OVector<R, U4> {
+        TransitionModelLinearNoControl::predict(self, state)
+    }
+    fn F_jacobian(&self, _state: &OVector<R, U4>) -> OMatrix<R, U4, U4> {
+        self.transition_model.clone()
+    }
+    fn Q(&self) -> &OMatrix<R, U4, U4> {
+        TransitionModelLinearNoControl::Q(self)
+    }
+}
+
+impl<R: RealField> NonlinearObservationModel<R, U4, U2> for PositionObservationModel<R>
+where
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U2, U4>,
+    DefaultAllocator: Allocator<R, U4, U2>,
+    DefaultAllocator: Allocator<R, U2, U2>,
+    DefaultAllocator: Allocator<R, U4>,
+    DefaultAllocator: Allocator<R, U2>,
+{
+    fn predict_observation(&self, state: &OVector<R, U4>) -> OVector<R, U2> {
+        ObservationModel::predict_observation(self, state)
+    }
+    fn H_jacobian(&self, _state: &OVector<R, U4>) -> OMatrix<R, U2, U4> {
+        self.observation_matrix.clone()
+    }
+    fn R(&self) -> &OMatrix<R, U2, U2> {
+        ObservationModel::R(self)
+    }
+}
+
+// Same constant-velocity motion model, but with a commanded 2D acceleration
+// `u = (ax, ay)` applied directly to the velocity components, `B` mapping
+// each acceleration component onto its corresponding velocity row over `dt`.
+struct ConstantVelocity2DModelWithControl<R>
+where
+    R: RealField,
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U4, U2>,
+    DefaultAllocator: Allocator<R, U4>,
+{
+    inner: ConstantVelocity2DModel<R>,
+    control_matrix: OMatrix<R, U4, U2>,
+}
+
+impl<R: RealField + Copy> ConstantVelocity2DModelWithControl<R> {
+    fn new(dt: R, noise_scale: R) -> Self {
+        let zero = na::convert(0.0);
+        #[rustfmt::skip]
+        let control_matrix = OMatrix::<R, U4, U2>::new(zero, zero,
+                                 zero, zero,
+                                 dt, zero,
+                                 zero, dt);
+        Self {
+            inner: ConstantVelocity2DModel::new(dt, noise_scale),
+            control_matrix,
+        }
+    }
+}
+
+impl<R: RealField> TransitionModelLinearWithControl<R, U4, U2>
+    for ConstantVelocity2DModelWithControl<R>
+where
+    R: RealField,
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U4, U2>,
+    DefaultAllocator: Allocator<R, U2, U4>,
+    DefaultAllocator: Allocator<R, U2, U2>,
+    DefaultAllocator: Allocator<R, U4>,
+    DefaultAllocator: Allocator<R, U2>,
+{
+    fn F(&self) -> &OMatrix<R, U4, U4> {
+        TransitionModelLinearNoControl::F(&self.inner)
+    }
+    fn FT(&self) -> &OMatrix<R, U4, U4> {
+        TransitionModelLinearNoControl::FT(&self.inner)
+    }
+    fn B(&self) -> &OMatrix<R, U4, U2> {
+        &self.control_matrix
+    }
+    fn Q(&self) -> &OMatrix<R, U4, U4> {
+        TransitionModelLinearNoControl::Q(&self.inner)
+    }
+}
+
+// A constant-velocity motion model whose `F`/`Q` are rebuilt from `dt` on
+// every call, rather than fixed at construction like
+// `ConstantVelocity2DModel`. `Q(dt)` is State Noise Compensation: the same
+// `t^3/3, t^2/2, dt` block structure, but regenerated from the continuous
+// acceleration PSD `noise_scale` and whatever `dt` this step reports.
+struct ConstantVelocity2DModelTimeVarying<R>
+where
+    R: RealField,
+{
+    noise_scale: R,
+}
+
+impl<R: RealField + Copy> ConstantVelocity2DModelTimeVarying<R> {
+    fn new(noise_scale: R) -> Self {
+        Self { noise_scale }
+    }
+}
+
+impl<R: RealField + Copy> TransitionModelLinearTimeVarying<R, U4>
+    for ConstantVelocity2DModelTimeVarying<R>
+{
+    fn F(&self, dt: R) -> OMatrix<R, U4, U4> {
+        let one = na::convert(1.0);
+        let zero = na::convert(0.0);
+        #[rustfmt::skip]
+        let f = OMatrix::<R,U4,U4>::new(one, zero,  dt, zero,
+                            zero, one, zero,  dt,
+                            zero, zero, one, zero,
+                            zero, zero, zero, one);
+        f
+    }
+    fn FT(&self, dt: R) -> OMatrix<R, U4, U4> {
+        self.F(dt).transpose()
+    }
+    fn Q(&self, dt: R) -> OMatrix<R, U4, U4> {
+        let zero = na::convert(0.0);
+        let t33 = dt * dt * dt / na::convert(3.0);
+        let t22 = dt * dt / na::convert(2.0);
+        #[rustfmt::skip]
+        let q = OMatrix::<R,U4,U4>::new(t33, zero, t22, zero,
+                                        zero, t33, zero, t22,
+                                        t22, zero, dt, zero,
+                                        zero, t22, zero, dt) * self.noise_scale;
+        q
+    }
+}
+
+#[test]
+fn test_ekf_matches_linear_kf_for_linear_model() {
+    let dt = 0.01;
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+
+    let motion_model = ConstantVelocity2DModel::new(dt, 100.0);
+    let observation_model = PositionObservationModel::new(0.01);
+    let kf = KalmanFilterNoControl::new(&motion_model, &observation_model);
+    let ekf = ExtendedKalmanFilterNoControl::new(&motion_model, &observation_model);
+
+    let mut kf_estimate =
+        adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+    let mut ekf_estimate =
+        adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+
+    let rdr = csv::Reader::from_reader(TRACKING_DATA.as_bytes());
+    for row in rdr.into_deserialize() {
+        let row: CsvRow = row.unwrap();
+        let this_observation = OVector::<f64, Const<2>>::new(row.obs_x, row.obs_y);
+
+        kf_estimate = kf.step(&kf_estimate, &this_observation).unwrap();
+        ekf_estimate = ekf.step(&ekf_estimate, &this_observation).unwrap();
+
+        assert_relative_eq!(
+            kf_estimate.state(),
+            ekf_estimate.state(),
+            max_relative = 1e-8
+        );
+        assert_relative_eq!(
+            kf_estimate.covariance(),
+            ekf_estimate.covariance(),
+            max_relative = 1e-8
+        );
+    }
+}
+
+#[test]
+fn test_step_with_gating_rejects_outlier() {
+    let dt = 0.01;
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+
+    let motion_model = ConstantVelocity2DModel::new(dt, 100.0);
+    let observation_model = PositionObservationModel::new(0.01);
+    let kf = KalmanFilterNoControl::new(&motion_model, &observation_model);
+
+    let estimate = adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+
+    // A chi-square quantile for 2 degrees of freedom at a stringent
+    // significance level; any reasonable, close-by observation should pass.
+    let gate = 13.8;
+
+    let reasonable_observation = OVector::<f64, Const<2>>::new(0.01, -0.01);
+    let accepted = kf
+        .step_with_gating(&estimate, &reasonable_observation, gate)
+        .unwrap();
+    assert!(!accepted.rejected());
+    assert_relative_eq!(
+        accepted.estimate().state(),
+        &kf.step(&estimate, &reasonable_observation)
+            .unwrap()
+            .state()
+            .clone(),
+        max_relative = 1e-8
+    );
+
+    // A wildly spurious detection, far outside the predicted uncertainty,
+    // should be rejected and leave the prior unchanged.
+    let spurious_observation = OVector::<f64, Const<2>>::new(1000.0, -1000.0);
+    let rejected = kf
+        .step_with_gating(&estimate, &spurious_observation, gate)
+        .unwrap();
+    assert!(rejected.rejected());
+    assert!(rejected.residual().normalized_innovation_squared().unwrap() > gate);
+
+    let prior_only = TransitionModelLinearNoControl::predict(&motion_model, estimate.state());
+    assert_relative_eq!(
+        rejected.estimate().state(),
+        &prior_only,
+        max_relative = 1e-8
+    );
+}
+
+// A constant-velocity model with purely diagonal process noise, so that
+// `UdKalmanFilter` (which only supports diagonal `Q`/`R`) can be checked
+// against the dense `KalmanFilterNoControl` on identical inputs.
+struct DiagonalNoiseModel<R>
+where
+    R: RealField,
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U4>,
+{
+    transition_model: OMatrix<R, U4, U4>,
+    transition_model_transpose: OMatrix<R, U4, U4>,
+    transition_noise_covariance: OMatrix<R, U4, U4>,
+}
+
+impl<R> DiagonalNoiseModel<R>
+where
+    R: RealField + Copy,
+{
+    fn new(dt: R, noise_var: R) -> Self {
+        let one = na::convert(1.0);
+        let zero = na::convert(0.0);
+        #[rustfmt::skip]
+        let transition_model = OMatrix::<R,U4,U4>::new(one, zero,  dt, zero,
+                            zero, one, zero,  dt,
+                            zero, zero, one, zero,
+                            zero, zero, zero, one);
+        let transition_noise_covariance = OMatrix::<R, U4, U4>::from_diagonal_element(noise_var);
+        Self {
+            transition_model,
+            transition_model_transpose: transition_model.transpose(),
+            transition_noise_covariance,
+        }
+    }
+}
+
+impl<R> TransitionModelLinearNoControl<R, U4> for DiagonalNoiseModel<R>
+where
+    R: RealField,
+    DefaultAllocator: Allocator<R, U4, U4>,
+    DefaultAllocator: Allocator<R, U4>,
+{
+    fn F(&self) -> &OMatrix<R, U4, U4> {
+        &self.transition_model
+    }
+    fn FT(&self) -> &OMatrix<R, U4, U4> {
+        &self.transition_model_transpose
+    }
+    fn Q(&self) -> &OMatrix<R, U4, U4> {
+        &self.transition_noise_covariance
+    }
+}
+
+#[test]
+fn test_ud_kalman_filter_matches_dense_kalman_filter() {
+    let dt = 0.01;
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+
+    let motion_model = DiagonalNoiseModel::new(dt, 100.0);
+    let observation_model = PositionObservationModel::new(0.01);
+    let kf = KalmanFilterNoControl::new(&motion_model, &observation_model);
+    let ud_kf = UdKalmanFilter::new(&motion_model, &observation_model);
+
+    let mut kf_estimate =
+        adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+    let mut ud_estimate =
+        adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+
+    let rdr = csv::Reader::from_reader(TRACKING_DATA.as_bytes());
+    for row in rdr.into_deserialize() {
+        let row: CsvRow = row.unwrap();
+        let this_observation = OVector::<f64, Const<2>>::new(row.obs_x, row.obs_y);
+
+        kf_estimate = kf.step(&kf_estimate, &this_observation).unwrap();
+        ud_estimate = ud_kf.step(&ud_estimate, &this_observation).unwrap();
+
+        assert_relative_eq!(
+            kf_estimate.state(),
+            ud_estimate.state(),
+            max_relative = 1e-6
+        );
+        assert_relative_eq!(
+            kf_estimate.covariance(),
+            ud_estimate.covariance(),
+            max_relative = 1e-6
+        );
+    }
+}
+
 fn check_covariance_update_method(covariance_update_method: &CovarianceUpdateMethod) {
     let dt = 0.01;
     let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
@@ -175,7 +507,7 @@ fn check_covariance_update_method(covariance_update_method: &CovarianceUpdateMet
     let maxerr = 1e-8;
 
     let rdr = csv::Reader::from_reader(TRACKING_DATA.as_bytes());
-    for row in rdr.into_deserialize().into_iter() {
+    for row in rdr.into_deserialize() {
         let row: CsvRow = row.unwrap();
         println!("{:?}", row);
         let this_observation = OVector::<f64, Const<2>>::new(row.obs_x, row.obs_y);
@@ -184,6 +516,7 @@ fn check_covariance_update_method(covariance_update_method: &CovarianceUpdateMet
                 &previous_estimate,
                 &this_observation,
                 *covariance_update_method,
+                None,
             )
             .unwrap();
 
@@ -211,6 +544,431 @@ fn test_covariance_update_methods() {
     }
 }
 
+#[test]
+fn test_reciprocal_condition_and_rcond_floor() {
+    let dt = 0.01;
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+
+    // A healthy, isotropic covariance is perfectly conditioned.
+    let healthy = adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+    assert_relative_eq!(healthy.reciprocal_condition(), 1.0, max_relative = 1e-8);
+
+    // An indefinite "covariance" (not positive semi-definite) is flagged via
+    // a negative reciprocal condition number.
+    #[rustfmt::skip]
+    let indefinite = OMatrix::<f64,U4,U4>::new(1.0, 0.0, 0.0, 0.0,
+        0.0, 1.0, 0.0, 0.0,
+        0.0, 0.0, -1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0);
+    let diverged = adskalman::StateAndCovariance::new(true_initial_state, indefinite);
+    assert!(diverged.reciprocal_condition() < 0.0);
+
+    // A negative-*definite* covariance (every eigenvalue negative, so the
+    // naive min/max ratio would be positive) is also flagged as negative.
+    #[rustfmt::skip]
+    let negative_definite = OMatrix::<f64,U4,U4>::new(-1.0, 0.0, 0.0, 0.0,
+        0.0, -2.0, 0.0, 0.0,
+        0.0, 0.0, -3.0, 0.0,
+        0.0, 0.0, 0.0, -4.0);
+    let all_negative = adskalman::StateAndCovariance::new(true_initial_state, negative_definite);
+    assert!(all_negative.reciprocal_condition() < 0.0);
+
+    // A singular (all-zero) covariance would divide zero by zero under a
+    // naive ratio; it must come out non-positive rather than `NaN`, so it
+    // cannot silently slip past a `rcond_floor` check.
+    let singular =
+        adskalman::StateAndCovariance::new(true_initial_state, OMatrix::<f64, U4, U4>::zeros());
+    assert!(singular.reciprocal_condition() <= 0.0);
+
+    let motion_model = ConstantVelocity2DModel::new(dt, 100.0);
+    let observation_model = PositionObservationModel::new(0.01);
+    let kf = KalmanFilterNoControl::new(&motion_model, &observation_model);
+    let observation = OVector::<f64, Const<2>>::new(0.01, -0.01);
+
+    // A lenient floor does not interfere with a normal step.
+    let lenient = kf
+        .step_with_options(
+            &healthy,
+            &observation,
+            CovarianceUpdateMethod::OptimalKalman,
+            Some(-1.0),
+        )
+        .unwrap();
+    assert_relative_eq!(
+        lenient.state(),
+        kf.step(&healthy, &observation).unwrap().state(),
+        max_relative = 1e-12
+    );
+
+    // An impossibly strict floor rejects the same step with a dedicated error.
+    let err = kf
+        .step_with_options(
+            &healthy,
+            &observation,
+            CovarianceUpdateMethod::OptimalKalman,
+            Some(2.0),
+        )
+        .unwrap_err();
+    assert_eq!(err, Error::CovarianceIllConditioned);
+}
+
+#[test]
+fn test_kalman_filter_with_control_zero_control_matches_no_control() {
+    let dt = 0.01;
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+    let initial_estimate =
+        adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+
+    let motion_model = ConstantVelocity2DModel::new(dt, 100.0);
+    let observation_model = PositionObservationModel::new(0.01);
+    let kf = KalmanFilterNoControl::new(&motion_model, &observation_model);
+
+    let control_motion_model = ConstantVelocity2DModelWithControl::new(dt, 100.0);
+    let control_kf = KalmanFilterWithControl::new(&control_motion_model, &observation_model);
+    let zero_control = OVector::<f64, U2>::zeros();
+
+    let mut no_control_estimate = initial_estimate.clone();
+    let mut with_control_estimate = initial_estimate;
+    for i in 0..20 {
+        let observation = OVector::<f64, Const<2>>::new(0.01 * i as f64, -0.01 * i as f64);
+        no_control_estimate = kf.step(&no_control_estimate, &observation).unwrap();
+        with_control_estimate = control_kf
+            .step(&with_control_estimate, &zero_control, &observation)
+            .unwrap();
+        assert_relative_eq!(
+            no_control_estimate.state(),
+            with_control_estimate.state(),
+            max_relative = 1e-8
+        );
+        assert_relative_eq!(
+            no_control_estimate.covariance(),
+            with_control_estimate.covariance(),
+            max_relative = 1e-8
+        );
+    }
+}
+
+#[test]
+fn test_kalman_filter_with_control_applies_control_input() {
+    let dt = 0.01;
+    let motion_model = ConstantVelocity2DModelWithControl::new(dt, 100.0);
+    let observation_model = PositionObservationModel::new(0.01);
+    let kf = KalmanFilterWithControl::new(&motion_model, &observation_model);
+
+    let estimate = adskalman::StateAndCovariance::new(
+        OVector::<f64, U4>::zeros(),
+        OMatrix::<f64, U4, U4>::identity() * 0.1,
+    );
+    let control = OVector::<f64, U2>::new(5.0, -2.0);
+    // A missing (NaN) observation leaves the predicted estimate unchanged, so
+    // this isolates the control input's effect on prediction: `x̄ = F x + B u`
+    // with `x = 0` reduces to `x̄ = B u`.
+    let missing_observation = OVector::<f64, Const<2>>::new(f64::NAN, f64::NAN);
+    let result = kf.step(&estimate, &control, &missing_observation).unwrap();
+
+    let expected = OVector::<f64, U4>::new(0.0, 0.0, dt * 5.0, dt * -2.0);
+    assert_relative_eq!(result.state(), &expected, max_relative = 1e-12);
+}
+
+#[test]
+fn test_kalman_filter_time_varying_matches_fixed_dt_at_constant_dt() {
+    let dt = 0.01;
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+    let initial_estimate =
+        adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+
+    let fixed_model = ConstantVelocity2DModel::new(dt, 100.0);
+    let observation_model = PositionObservationModel::new(0.01);
+    let fixed_kf = KalmanFilterNoControl::new(&fixed_model, &observation_model);
+
+    let time_varying_model = ConstantVelocity2DModelTimeVarying::new(100.0);
+    let time_varying_kf = KalmanFilterTimeVarying::new(&time_varying_model, &observation_model);
+
+    // At a constant `dt`, the SNC-rebuilt `F(dt)`/`Q(dt)` exactly reproduce
+    // the matrices `ConstantVelocity2DModel` precomputes once.
+    assert_relative_eq!(
+        time_varying_model.F(dt),
+        TransitionModelLinearNoControl::F(&fixed_model),
+        max_relative = 1e-12
+    );
+    assert_relative_eq!(
+        time_varying_model.Q(dt),
+        TransitionModelLinearNoControl::Q(&fixed_model),
+        max_relative = 1e-12
+    );
+
+    let mut fixed_estimate = initial_estimate.clone();
+    let mut time_varying_estimate = initial_estimate;
+    for i in 0..20 {
+        let observation = OVector::<f64, Const<2>>::new(0.01 * i as f64, -0.01 * i as f64);
+        fixed_estimate = fixed_kf.step(&fixed_estimate, &observation).unwrap();
+        time_varying_estimate = time_varying_kf
+            .step_with_dt(&time_varying_estimate, &observation, dt)
+            .unwrap();
+        assert_relative_eq!(
+            fixed_estimate.state(),
+            time_varying_estimate.state(),
+            max_relative = 1e-8
+        );
+        assert_relative_eq!(
+            fixed_estimate.covariance(),
+            time_varying_estimate.covariance(),
+            max_relative = 1e-8
+        );
+    }
+}
+
+#[test]
+fn test_kalman_filter_time_varying_handles_irregular_dt() {
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+    let mut estimate = adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+
+    let motion_model = ConstantVelocity2DModelTimeVarying::new(100.0);
+    let observation_model = PositionObservationModel::new(0.01);
+    let kf = KalmanFilterTimeVarying::new(&motion_model, &observation_model);
+
+    // Irregular, asynchronous measurement timing: a large step followed by
+    // steps of differing, smaller lengths.
+    let dts = [0.1, 0.01, 0.001, 0.05];
+    for (i, &dt) in dts.iter().enumerate() {
+        let observation = OVector::<f64, Const<2>>::new(0.01 * i as f64, -0.01 * i as f64);
+        estimate = kf.step_with_dt(&estimate, &observation, dt).unwrap();
+        assert!(estimate.state().iter().all(|v| v.is_finite()));
+        assert!(estimate.reciprocal_condition() > 0.0);
+    }
+}
+
+// A dynamically-sized observation model, observing an arbitrary number of
+// state components directly. Used to exercise `step_multi`, where sensors
+// of differing dimension must share a single slice via `OS = Dyn`.
+struct DynObservationModel<R: RealField>
+where
+    DefaultAllocator: Allocator<R, U4, U4>
+        + Allocator<R, Dyn, U4>
+        + Allocator<R, U4, Dyn>
+        + Allocator<R, Dyn, Dyn>
+        + Allocator<R, U4>
+        + Allocator<R, Dyn>,
+{
+    observation_matrix: OMatrix<R, Dyn, U4>,
+    observation_matrix_transpose: OMatrix<R, U4, Dyn>,
+    observation_noise_covariance: OMatrix<R, Dyn, Dyn>,
+}
+
+impl<R: RealField + Copy> DynObservationModel<R> {
+    /// Observes the 2D position (rows 0, 1).
+    fn position(var: R) -> Self {
+        let one: R = na::convert(1.0);
+        let zero: R = na::convert(0.0);
+        #[rustfmt::skip]
+        let observation_matrix = OMatrix::<R, Dyn, U4>::from_row_slice(&[
+            one, zero, zero, zero,
+            zero, one, zero, zero,
+        ]);
+        let observation_noise_covariance =
+            OMatrix::<R, Dyn, Dyn>::from_row_slice(2, 2, &[var, zero, zero, var]);
+        let observation_matrix_transpose = observation_matrix.transpose();
+        Self {
+            observation_matrix,
+            observation_matrix_transpose,
+            observation_noise_covariance,
+        }
+    }
+
+    /// Observes only the x position (row 0).
+    fn x_position(var: R) -> Self {
+        let one: R = na::convert(1.0);
+        let zero: R = na::convert(0.0);
+        #[rustfmt::skip]
+        let observation_matrix =
+            OMatrix::<R, Dyn, U4>::from_row_slice(&[one, zero, zero, zero]);
+        let observation_noise_covariance = OMatrix::<R, Dyn, Dyn>::from_row_slice(1, 1, &[var]);
+        let observation_matrix_transpose = observation_matrix.transpose();
+        Self {
+            observation_matrix,
+            observation_matrix_transpose,
+            observation_noise_covariance,
+        }
+    }
+}
+
+impl<R: RealField> ObservationModel<R, U4, Dyn> for DynObservationModel<R>
+where
+    DefaultAllocator: Allocator<R, U4, U4>
+        + Allocator<R, Dyn, U4>
+        + Allocator<R, U4, Dyn>
+        + Allocator<R, Dyn, Dyn>
+        + Allocator<R, U4>
+        + Allocator<R, Dyn>
+        + Allocator<(usize, usize), Dyn>,
+    Dyn: DimMin<Dyn, Output = Dyn>,
+{
+    fn H(&self) -> &OMatrix<R, Dyn, U4> {
+        &self.observation_matrix
+    }
+    fn HT(&self) -> &OMatrix<R, U4, Dyn> {
+        &self.observation_matrix_transpose
+    }
+    fn R(&self) -> &OMatrix<R, Dyn, Dyn> {
+        &self.observation_noise_covariance
+    }
+}
+
+#[test]
+fn test_step_multi_single_sensor_matches_step() {
+    let dt = 0.01;
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+    let initial_estimate =
+        adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+
+    let motion_model = ConstantVelocity2DModel::new(dt, 100.0);
+    let position_model = PositionObservationModel::new(0.01);
+    let kf = KalmanFilterNoControl::new(&motion_model, &position_model);
+
+    let position_obs = OVector::<f64, Const<2>>::new(0.05, -0.03);
+    let via_step = kf.step(&initial_estimate, &position_obs).unwrap();
+
+    let dyn_position_model = DynObservationModel::position(0.01);
+    let dyn_obs = OVector::<f64, Dyn>::from_column_slice(position_obs.as_slice());
+    let via_step_multi = kf
+        .step_multi(
+            &initial_estimate,
+            &[(
+                &dyn_position_model as &dyn ObservationModel<f64, U4, Dyn>,
+                Some(dyn_obs),
+            )],
+        )
+        .unwrap();
+
+    assert_relative_eq!(
+        via_step.state(),
+        via_step_multi.state(),
+        max_relative = 1e-10
+    );
+    assert_relative_eq!(
+        via_step.covariance(),
+        via_step_multi.covariance(),
+        max_relative = 1e-10
+    );
+}
+
+#[test]
+fn test_step_multi_fuses_heterogeneous_sensors_sequentially() {
+    let dt = 0.01;
+    let true_initial_state = OVector::<f64, U4>::new(0.0, 0.0, 10.0, -5.0);
+    #[rustfmt::skip]
+    let initial_covariance = OMatrix::<f64,U4,U4>::new(0.1, 0.0, 0.0, 0.0,
+        0.0, 0.1, 0.0, 0.0,
+        0.0, 0.0, 0.1, 0.0,
+        0.0, 0.0, 0.0, 0.1);
+    let initial_estimate =
+        adskalman::StateAndCovariance::new(true_initial_state, initial_covariance);
+
+    let motion_model = ConstantVelocity2DModel::new(dt, 100.0);
+    let position_model = PositionObservationModel::new(0.01);
+    let kf = KalmanFilterNoControl::new(&motion_model, &position_model);
+
+    // A 2D position sensor and a 1D x-only sensor, of differing
+    // dimensions, fused together within one `step_multi` call.
+    let dyn_position_model = DynObservationModel::position(0.01);
+    let dyn_x_model = DynObservationModel::x_position(0.0001);
+    let position_obs = OVector::<f64, Dyn>::from_column_slice(&[0.05, -0.03]);
+    let x_obs = OVector::<f64, Dyn>::from_column_slice(&[0.052]);
+
+    let fused = kf
+        .step_multi(
+            &initial_estimate,
+            &[
+                (
+                    &dyn_position_model as &dyn ObservationModel<f64, U4, Dyn>,
+                    Some(position_obs.clone()),
+                ),
+                (
+                    &dyn_x_model as &dyn ObservationModel<f64, U4, Dyn>,
+                    Some(x_obs.clone()),
+                ),
+            ],
+        )
+        .unwrap();
+
+    // Reproduce the same two sequential updates by hand, starting from the
+    // same predicted prior and applying the second update to the result of
+    // the first, and check `step_multi` agrees exactly.
+    let prior = kf.step_multi(&initial_estimate, &[]).unwrap();
+    let h1 = dyn_position_model.H();
+    let r1 = dyn_position_model.R();
+    let innovation1 = &position_obs - h1 * prior.state();
+    let s1 = h1 * prior.covariance() * h1.transpose() + r1;
+    let k1 = prior.covariance() * h1.transpose() * s1.try_inverse().unwrap();
+    let state1 = prior.state() + &k1 * innovation1;
+    let covariance1 = prior.covariance() - &k1 * h1 * prior.covariance();
+
+    let h2 = dyn_x_model.H();
+    let r2 = dyn_x_model.R();
+    let innovation2 = &x_obs - h2 * state1;
+    let s2 = h2 * covariance1 * h2.transpose() + r2;
+    let k2 = covariance1 * h2.transpose() * s2.try_inverse().unwrap();
+    let state2 = state1 + &k2 * innovation2;
+    let covariance2 = covariance1 - &k2 * h2 * covariance1;
+
+    assert_relative_eq!(fused.state(), &state2, max_relative = 1e-10);
+    assert_relative_eq!(fused.covariance(), &covariance2, max_relative = 1e-10);
+
+    // A sensor with `None` is skipped entirely: fusing only the position
+    // sensor with the x-sensor absent matches the single-sensor case.
+    let position_only = kf
+        .step_multi(
+            &initial_estimate,
+            &[
+                (
+                    &dyn_position_model as &dyn ObservationModel<f64, U4, Dyn>,
+                    Some(position_obs),
+                ),
+                (&dyn_x_model as &dyn ObservationModel<f64, U4, Dyn>, None),
+            ],
+        )
+        .unwrap();
+    let via_step = kf
+        .step_multi(
+            &initial_estimate,
+            &[(
+                &dyn_position_model as &dyn ObservationModel<f64, U4, Dyn>,
+                Some(OVector::<f64, Dyn>::from_column_slice(&[0.05, -0.03])),
+            )],
+        )
+        .unwrap();
+    assert_relative_eq!(
+        position_only.state(),
+        via_step.state(),
+        max_relative = 1e-10
+    );
+}
+
 #[test]
 fn test_offline_smoothing() {
     let dt = 0.01;
@@ -229,7 +987,7 @@ fn test_offline_smoothing() {
     let mut expected = vec![];
 
     let rdr = csv::Reader::from_reader(SMOOTHED_DATA.as_bytes());
-    for row in rdr.into_deserialize().into_iter() {
+    for row in rdr.into_deserialize() {
         let row: CsvRow = row.unwrap();
 
         println!("{:?}", row);
@@ -274,7 +1032,7 @@ fn test_offline_smoothing_with_missing_data() {
     let mut expected = vec![];
 
     let rdr = csv::Reader::from_reader(SMOOTHED_DATA.as_bytes());
-    for row in rdr.into_deserialize().into_iter() {
+    for row in rdr.into_deserialize() {
         let row: CsvRow = row.unwrap();
 
         println!("{:?}", row);
@@ -289,8 +1047,8 @@ fn test_offline_smoothing_with_missing_data() {
     }
 
     assert_eq!(observation.len(), 50);
-    for i in 25..30 {
-        observation[i] = OVector::<f64, Const<2>>::new(std::f64::NAN, std::f64::NAN);
+    for obs in &mut observation[25..30] {
+        *obs = OVector::<f64, Const<2>>::new(f64::NAN, f64::NAN);
     }
 
     let initial_estimate =